@@ -0,0 +1,181 @@
+use rand::prelude::*;
+
+use crate::board::Board;
+use crate::number::Number;
+use crate::square::*;
+
+/// 1回の焼きなましで行う近傍操作の回数。
+const ITERATIONS: u32 = 200_000;
+/// 開始温度。
+const T0: f64 = 1.0;
+/// 終了温度。
+const T1: f64 = 1e-3;
+
+/// 焼きなまし法 (Simulated Annealing) によって局面を解く。
+///
+/// 各ブロックを、そのブロックに欠けている数のランダム順列で埋めて初期状態とする
+/// (ヒントは固定するため、ブロック制約は常に満たされる)。エネルギーを
+/// 行・列に現れる重複数の総数と定義し、同一ブロック内の非ヒントマス2つを
+/// 入れ替える近傍操作を `exp(-Δ/T)` の Metropolis 基準で受理しながら、
+/// `T0` から `T1` まで幾何的に冷却した温度のもとで局所探索する。
+/// エネルギーが0になった時点で解として返す。局所最小のまま冷え切った場合は
+/// 新しい初期状態から焼きなまし直す。
+pub(crate) fn solve(given: &Board) -> Board {
+    let mut rng = thread_rng();
+    let cooling = (T1 / T0).powf(1.0 / f64::from(ITERATIONS));
+
+    loop {
+        let mut state = seed(given, &mut rng);
+        let mut energy = calc_energy(&state);
+        let mut t = T0;
+
+        for _ in 0..ITERATIONS {
+            if energy == 0 {
+                return to_board(&state);
+            }
+
+            let Some((sq1, sq2)) = choose_swap(given, &mut rng) else {
+                t *= cooling;
+                continue;
+            };
+
+            let delta = swap_delta(&state, sq1, sq2);
+            if delta <= 0 || rng.gen::<f64>() < (-f64::from(delta) / t).exp() {
+                state.swap(sq1, sq2);
+                energy = (i64::from(energy) + i64::from(delta)) as u32;
+            }
+
+            t *= cooling;
+        }
+
+        if energy == 0 {
+            return to_board(&state);
+        }
+        // 冷え切っても解けなければ、新しい初期状態からやり直す。
+    }
+}
+
+/// 数が既に埋まっている盤面を表す状態。
+#[derive(Clone)]
+struct State([Number; Square::NUM]);
+
+impl State {
+    fn get(&self, sq: Square) -> Number {
+        self.0[usize::from(sq.get())]
+    }
+
+    fn set(&mut self, sq: Square, num: Number) {
+        self.0[usize::from(sq.get())] = num;
+    }
+
+    fn swap(&mut self, sq1: Square, sq2: Square) {
+        self.0.swap(usize::from(sq1.get()), usize::from(sq2.get()));
+    }
+}
+
+/// ヒントを固定したまま、各ブロックを欠けている数のランダム順列で埋める。
+fn seed<R: Rng>(given: &Board, rng: &mut R) -> State {
+    let mut state = State([unsafe { Number::new_unchecked(1) }; Square::NUM]);
+
+    for block in Block::all() {
+        let mut used = [false; Number::NUM];
+        let mut empty_cells = Vec::new();
+
+        for sq in Square::block_all(block) {
+            match given[sq] {
+                Some(num) => {
+                    state.set(sq, num);
+                    used[usize::from(num.get() - 1)] = true;
+                }
+                None => empty_cells.push(sq),
+            }
+        }
+
+        let mut remaining: Vec<Number> = Number::all()
+            .into_iter()
+            .filter(|num| !used[usize::from(num.get() - 1)])
+            .collect();
+        remaining.shuffle(rng);
+
+        for (sq, num) in empty_cells.into_iter().zip(remaining) {
+            state.set(sq, num);
+        }
+    }
+
+    state
+}
+
+/// 同一ブロック内の非ヒントマスを2つランダムに選ぶ。選べなければ `None`。
+fn choose_swap<R: Rng>(given: &Board, rng: &mut R) -> Option<(Square, Square)> {
+    let block = Block::all().choose(rng).copied()?;
+    let swappable: Vec<Square> = Square::block_all(block)
+        .into_iter()
+        .filter(|&sq| given[sq].is_none())
+        .collect();
+
+    if swappable.len() < 2 {
+        return None;
+    }
+
+    let (i, j) = loop {
+        let i = rng.gen_range(0..swappable.len());
+        let j = rng.gen_range(0..swappable.len());
+        if i != j {
+            break (i, j);
+        }
+    };
+    Some((swappable[i], swappable[j]))
+}
+
+/// `sq1`, `sq2` を入れ替えた場合のエネルギー変化量を求める。
+/// 影響を受けるのは両者が属する行・列のみなので、それらだけを再計算する。
+fn swap_delta(state: &State, sq1: Square, sq2: Square) -> i32 {
+    let before = affected_energy(state, sq1, sq2);
+
+    let mut swapped = state.clone();
+    swapped.swap(sq1, sq2);
+    let after = affected_energy(&swapped, sq1, sq2);
+
+    after as i32 - before as i32
+}
+
+fn affected_energy(state: &State, sq1: Square, sq2: Square) -> u32 {
+    let mut energy = unit_energy(state, &Square::row_all(sq1.row()));
+    energy += unit_energy(state, &Square::col_all(sq1.col()));
+    if sq2.row().get() != sq1.row().get() {
+        energy += unit_energy(state, &Square::row_all(sq2.row()));
+    }
+    if sq2.col().get() != sq1.col().get() {
+        energy += unit_energy(state, &Square::col_all(sq2.col()));
+    }
+    energy
+}
+
+/// 盤面全体のエネルギー(行・列に現れる重複数の総数)を求める。
+fn calc_energy(state: &State) -> u32 {
+    let mut energy = 0;
+    for col in Col::all() {
+        energy += unit_energy(state, &Square::col_all(col));
+    }
+    for row in Row::all() {
+        energy += unit_energy(state, &Square::row_all(row));
+    }
+    energy
+}
+
+/// 1つのユニット(行 or 列)に現れる重複数の個数を求める。
+fn unit_energy(state: &State, squares: &[Square; 9]) -> u32 {
+    let mut counts = [0_u32; Number::NUM];
+    for &sq in squares {
+        counts[usize::from(state.get(sq).get() - 1)] += 1;
+    }
+    counts.iter().filter(|&&c| c >= 2).map(|&c| c - 1).sum()
+}
+
+fn to_board(state: &State) -> Board {
+    let mut inner = [None; Square::NUM];
+    for sq in Square::all() {
+        inner[usize::from(sq.get())] = Some(state.get(sq));
+    }
+    Board::new(inner).expect("焼きなましで得られた解が数独の制約を満たしていない")
+}
@@ -3,21 +3,44 @@ use anyhow::{bail, ensure};
 use crate::number::Number;
 use crate::square::*;
 
+pub use crate::group::Variant;
+
 /// 盤面。`Square` でインデックスアクセスできる。
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Board([Option<Number>; 81]);
+pub struct Board {
+    variant: Variant,
+    cells: [Option<Number>; 81],
+}
 
 impl Board {
     /// 空の盤面を返す。
     pub fn empty() -> Self {
-        Self([None; 81])
+        Self::empty_with_variant(Variant::Classic)
     }
 
-    /// 内部配列を与えて盤面を作る。盤面が不正ならエラーを返す。
+    /// バリアント `variant` における空の盤面を返す。
+    pub fn empty_with_variant(variant: Variant) -> Self {
+        Self {
+            variant,
+            cells: [None; 81],
+        }
+    }
+
+    /// 内部配列を与えて、通常ルールの盤面を作る。盤面が不正ならエラーを返す。
     pub fn new(inner: [Option<Number>; 81]) -> anyhow::Result<Self> {
-        let is_ok = |sqs: [Square; 9]| -> bool {
+        Self::new_with_variant(inner, Variant::Classic)
+    }
+
+    /// 内部配列とバリアントを与えて盤面を作る。盤面が不正ならエラーを返す。
+    ///
+    /// バリアントが課す全ての制約グループについて、重複する数がないことを検証する。
+    pub fn new_with_variant(
+        inner: [Option<Number>; 81],
+        variant: Variant,
+    ) -> anyhow::Result<Self> {
+        let is_ok = |sqs: &[Square]| -> bool {
             let mut mask = 0_u32;
-            for sq in sqs {
+            for &sq in sqs {
                 let Some(num) = inner[usize::from(sq.get())] else {
                     continue;
                 };
@@ -30,21 +53,19 @@ impl Board {
             true
         };
 
-        for col in Col::all() {
-            ensure!(is_ok(Square::col_all(col)), "col {} is illegal", col.get());
-        }
-        for row in Row::all() {
-            ensure!(is_ok(Square::row_all(row)), "row {} is illegal", row.get());
-        }
-        for block in Block::all() {
-            ensure!(
-                is_ok(Square::block_all(block)),
-                "block {} is illegal",
-                block.get()
-            );
+        for (i, group) in variant.groups().into_iter().enumerate() {
+            ensure!(is_ok(&group), "constraint group {} is illegal", i);
         }
 
-        Ok(Self(inner))
+        Ok(Self {
+            variant,
+            cells: inner,
+        })
+    }
+
+    /// 盤面のバリアントを返す。
+    pub fn variant(&self) -> Variant {
+        self.variant
     }
 
     /// 盤面が既に解けているかどうかを返す。
@@ -57,13 +78,13 @@ impl std::ops::Index<Square> for Board {
     type Output = Option<Number>;
 
     fn index(&self, sq: Square) -> &Self::Output {
-        &self.0[usize::from(sq.get())]
+        &self.cells[usize::from(sq.get())]
     }
 }
 
 impl std::ops::IndexMut<Square> for Board {
     fn index_mut(&mut self, sq: Square) -> &mut Self::Output {
-        &mut self.0[usize::from(sq.get())]
+        &mut self.cells[usize::from(sq.get())]
     }
 }
 
@@ -0,0 +1,234 @@
+use crate::board::Board;
+use crate::number::Number;
+use crate::square::Square;
+
+/// 制約列の総数 (マス埋め81 + 行×数81 + 列×数81 + ブロック×数81)。
+const NUM_COLUMNS: usize = 4 * 81;
+/// 候補行の総数 (マス × 数)。
+const NUM_ROWS: usize = Square::NUM * Number::NUM;
+
+/// ルートノードの番号。列ヘッダは `0..NUM_COLUMNS`、ルートは `NUM_COLUMNS`。
+const ROOT: usize = NUM_COLUMNS;
+
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    left: usize,
+    right: usize,
+    up: usize,
+    down: usize,
+    /// このノードが属する列ヘッダの番号。ヘッダノード自身では自分自身を指す。
+    column: usize,
+    /// このノードが属する候補行の番号 (`Square::NUM * Number::NUM` 未満)。
+    /// ヘッダノードでは無効。
+    row_id: usize,
+}
+
+/// Dancing Links (Algorithm X) による厳密被覆ソルバ。
+///
+/// 数独を、81 マス埋め + 81 行×数 + 81 列×数 + 81 ブロック×数 の計 324 列と、
+/// マス×数の組に対応する 729 候補行からなる厳密被覆問題としてモデル化する。
+/// 各候補行は自身が満たす 4 列にちょうど 1 個ずつノードをもち、
+/// 列方向・行方向それぞれ循環双方向リストで接続される。
+#[derive(Debug)]
+pub(crate) struct Dlx {
+    nodes: Vec<Node>,
+    size: Vec<u32>,
+    /// 候補行番号から、その行がもつ 4 ノードの番号を引く表。
+    row_nodes: Vec<[usize; 4]>,
+    /// 既に確定している(盤面の初期手がかりに対応する)候補行番号。
+    given_rows: Vec<usize>,
+}
+
+impl Dlx {
+    /// 盤面を基に、初期手がかりを覆った状態の `Dlx` を作る。
+    pub(crate) fn new(board: &Board) -> Self {
+        let mut dlx = Self::build_empty();
+
+        for sq in Square::all() {
+            if let Some(num) = board[sq] {
+                dlx.select_given(sq, num);
+            }
+        }
+
+        dlx
+    }
+
+    /// 何も覆っていない、324 列 729 行の完全な行列を作る。
+    fn build_empty() -> Self {
+        let mut nodes = Vec::with_capacity((NUM_COLUMNS + 1) + NUM_ROWS * 4);
+
+        // ルート(番号 NUM_COLUMNS) + 列ヘッダ(番号 0..NUM_COLUMNS) を作る。
+        for c in 0..=NUM_COLUMNS {
+            nodes.push(Node {
+                left: if c == 0 { NUM_COLUMNS } else { c - 1 },
+                right: if c == NUM_COLUMNS { 0 } else { c + 1 },
+                up: c,
+                down: c,
+                column: c,
+                row_id: usize::MAX,
+            });
+        }
+
+        let mut size = vec![0_u32; NUM_COLUMNS];
+        let mut row_nodes = Vec::with_capacity(NUM_ROWS);
+
+        for sq_idx in 0..Square::NUM {
+            let sq = unsafe { Square::new_unchecked(sq_idx as u8) };
+
+            for num_idx in 0..Number::NUM {
+                let row_id = sq_idx * Number::NUM + num_idx;
+
+                let columns = [
+                    sq_idx,
+                    81 + usize::from(sq.row().get()) * 9 + num_idx,
+                    162 + usize::from(sq.col().get()) * 9 + num_idx,
+                    243 + usize::from(sq.block().get()) * 9 + num_idx,
+                ];
+
+                let mut row_node_ids = [0_usize; 4];
+                for (k, &c) in columns.iter().enumerate() {
+                    let node_id = nodes.len();
+                    let up = nodes[c].up;
+                    nodes.push(Node {
+                        left: 0,
+                        right: 0,
+                        up,
+                        down: c,
+                        column: c,
+                        row_id,
+                    });
+                    nodes[up].down = node_id;
+                    nodes[c].up = node_id;
+                    size[c] += 1;
+                    row_node_ids[k] = node_id;
+                }
+                for k in 0..4 {
+                    let prev = row_node_ids[(k + 3) % 4];
+                    let next = row_node_ids[(k + 1) % 4];
+                    nodes[row_node_ids[k]].left = prev;
+                    nodes[row_node_ids[k]].right = next;
+                }
+
+                row_nodes.push(row_node_ids);
+            }
+        }
+
+        Self {
+            nodes,
+            size,
+            row_nodes,
+            given_rows: Vec::new(),
+        }
+    }
+
+    /// 候補行 `(sq, num)` を確定済みとして覆う。
+    fn select_given(&mut self, sq: Square, num: Number) {
+        let row_id = usize::from(sq.get()) * Number::NUM + usize::from(num.get() - 1);
+        let row_node_ids = self.row_nodes[row_id];
+        for node_id in row_node_ids {
+            self.cover(self.nodes[node_id].column);
+        }
+        self.given_rows.push(row_id);
+    }
+
+    /// 列 `c` を隠す。`c` に乗っている行はすべて、他の列からも隠される。
+    fn cover(&mut self, c: usize) {
+        let l = self.nodes[c].left;
+        let r = self.nodes[c].right;
+        self.nodes[r].left = l;
+        self.nodes[l].right = r;
+
+        let mut i = self.nodes[c].down;
+        while i != c {
+            let mut j = self.nodes[i].right;
+            while j != i {
+                let u = self.nodes[j].up;
+                let d = self.nodes[j].down;
+                self.nodes[d].up = u;
+                self.nodes[u].down = d;
+                self.size[self.nodes[j].column] -= 1;
+                j = self.nodes[j].right;
+            }
+            i = self.nodes[i].down;
+        }
+    }
+
+    /// `cover` の逆操作。必ず `cover` と対になる順序(LIFO)で呼ぶこと。
+    fn uncover(&mut self, c: usize) {
+        let mut i = self.nodes[c].up;
+        while i != c {
+            let mut j = self.nodes[i].left;
+            while j != i {
+                self.size[self.nodes[j].column] += 1;
+                let u = self.nodes[j].up;
+                let d = self.nodes[j].down;
+                self.nodes[d].up = j;
+                self.nodes[u].down = j;
+                j = self.nodes[j].left;
+            }
+            i = self.nodes[i].up;
+        }
+
+        let l = self.nodes[c].left;
+        let r = self.nodes[c].right;
+        self.nodes[r].left = c;
+        self.nodes[l].right = c;
+    }
+
+    /// 最初に見つかった厳密被覆を探し、その候補行番号の一覧(初期手がかりを含む)を返す。
+    pub(crate) fn solve_first(&mut self) -> Option<Vec<usize>> {
+        let mut solution = Vec::new();
+        if !self.search(&mut solution) {
+            return None;
+        }
+
+        let mut result = self.given_rows.clone();
+        result.extend(solution);
+        Some(result)
+    }
+
+    fn search(&mut self, solution: &mut Vec<usize>) -> bool {
+        if self.nodes[ROOT].right == ROOT {
+            return true;
+        }
+
+        // サイズ最小の列を選ぶ。
+        let mut c = self.nodes[ROOT].right;
+        let mut j = self.nodes[c].right;
+        while j != ROOT {
+            if self.size[j] < self.size[c] {
+                c = j;
+            }
+            j = self.nodes[j].right;
+        }
+
+        self.cover(c);
+
+        let mut r = self.nodes[c].down;
+        while r != c {
+            solution.push(self.nodes[r].row_id);
+
+            let mut j = self.nodes[r].right;
+            while j != r {
+                self.cover(self.nodes[j].column);
+                j = self.nodes[j].right;
+            }
+
+            if self.search(solution) {
+                return true;
+            }
+
+            solution.pop();
+            let mut j = self.nodes[r].left;
+            while j != r {
+                self.uncover(self.nodes[j].column);
+                j = self.nodes[j].left;
+            }
+
+            r = self.nodes[r].down;
+        }
+
+        self.uncover(c);
+        false
+    }
+}
@@ -0,0 +1,179 @@
+use std::time::{Duration, Instant};
+
+use rand::prelude::*;
+
+use crate::board::Board;
+use crate::square::Square;
+use crate::sudoku::Sudoku;
+use crate::symmetry::Symmetry;
+
+/// 一意解でなくなった局面に課すエネルギーの罰則。
+const PENALTY_NOT_UNIQUE: i64 = 1000;
+/// 開始温度。
+const T0: f64 = 2.0;
+/// 終了温度。
+const T1: f64 = 0.01;
+/// 改善がこの回数続けて得られなければ、新しい完成盤から焼きなまし直す。
+const STALL_LIMIT: u32 = 2000;
+
+/// 焼きなまし法によって、ヒント数最小かつ一意解の問題を生成する。
+///
+/// 状態は完成盤に対する「ヒントとして見せるマスの集合」(マスク)とし、
+/// エネルギーを `ヒント数 + P・(一意解でなければ1、そうでなければ0)` と定義する。
+/// 近傍操作はマスク上の1マス(symmetry指定時はその軌道全体)のオン/オフ切替で、
+/// `exp(-ΔE/T)` の Metropolis 基準で受理しながら、`T0` から `T1` まで
+/// 経過時間に応じて幾何的に冷却する。局所最小で改善が止まったら、新しい
+/// ランダムな完成盤から焼きなまし直し(多スタート)、最良のマスクを保持し続ける。
+#[derive(Clone, Copy, Debug)]
+pub struct Generator {
+    hint_min: u32,
+    symmetry: Option<Symmetry>,
+}
+
+impl Generator {
+    /// デフォルト設定の `Generator` を作る。
+    pub fn new() -> Self {
+        Self {
+            hint_min: 17,
+            symmetry: None,
+        }
+    }
+
+    /// 最小ヒント数を指定する。
+    pub fn hint_min(mut self, hint_min: u32) -> Self {
+        self.hint_min = hint_min;
+        self
+    }
+
+    /// 維持すべき対称性を指定する。
+    pub fn symmetry(mut self, symmetry: Symmetry) -> Self {
+        self.symmetry = Some(symmetry);
+        self
+    }
+
+    /// `budget` の時間をかけて問題を1つ生成する。`seed` は乱数シード。
+    pub fn generate(&self, budget: Duration, seed: u64) -> Board {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let deadline = Instant::now() + budget;
+
+        let mut best: Option<(Board, [bool; Square::NUM], i64)> = None;
+
+        loop {
+            let (solution, mask, energy) = self.anneal(&mut rng, deadline, budget);
+            let is_better = match &best {
+                Some((_, _, best_energy)) => energy < *best_energy,
+                None => true,
+            };
+            if is_better {
+                best = Some((solution, mask, energy));
+            }
+            if Instant::now() >= deadline {
+                break;
+            }
+        }
+
+        let (solution, mask, _) = best.expect("焼きなましを少なくとも1回は行う");
+        mask_to_board(&solution, &mask)
+    }
+
+    /// 1回分の焼きなまし(完成盤1枚に対する局所探索)を行い、
+    /// 完成盤・到達したマスク・そのエネルギーを返す。
+    fn anneal(
+        &self,
+        rng: &mut StdRng,
+        deadline: Instant,
+        budget: Duration,
+    ) -> (Board, [bool; Square::NUM], i64) {
+        let (seed_sudoku, _) = Sudoku::generate_unique(81);
+        let solution = seed_sudoku.board().clone();
+
+        let mut mask = [true; Square::NUM];
+        let mut energy = self.clue_count(&mask) as i64;
+
+        let mut best_mask = mask;
+        let mut best_energy = energy;
+        let mut stall = 0;
+
+        let start = Instant::now();
+        while Instant::now() < deadline && stall < STALL_LIMIT {
+            let elapsed = start.elapsed().as_secs_f64() / budget.as_secs_f64().max(1e-9);
+            let t = T0 * (T1 / T0).powf(elapsed.min(1.0));
+
+            let orbit = self.orbit_of(Square::all().choose(rng).copied().unwrap());
+            let turning_on = !mask[usize::from(orbit[0].get())];
+
+            if !turning_on {
+                let new_count = self.clue_count(&mask) - orbit.len() as u32;
+                if new_count < self.hint_min {
+                    continue;
+                }
+            }
+
+            for &sq in &orbit {
+                mask[usize::from(sq.get())] = turning_on;
+            }
+
+            let new_unique = if turning_on {
+                true
+            } else {
+                self.is_unique(&solution, &mask)
+            };
+            let new_energy =
+                self.clue_count(&mask) as i64 + if new_unique { 0 } else { PENALTY_NOT_UNIQUE };
+
+            let delta = new_energy - energy;
+            let accept = delta <= 0 || rng.gen::<f64>() < (-(delta as f64) / t).exp();
+
+            if accept {
+                energy = new_energy;
+                if energy < best_energy {
+                    best_energy = energy;
+                    best_mask = mask;
+                    stall = 0;
+                } else {
+                    stall += 1;
+                }
+            } else {
+                for &sq in &orbit {
+                    mask[usize::from(sq.get())] = !turning_on;
+                }
+                stall += 1;
+            }
+        }
+
+        (solution, best_mask, best_energy)
+    }
+
+    fn orbit_of(&self, sq: Square) -> Vec<Square> {
+        match self.symmetry {
+            Some(symmetry) => symmetry.orbit(sq),
+            None => vec![sq],
+        }
+    }
+
+    fn clue_count(&self, mask: &[bool; Square::NUM]) -> u32 {
+        mask.iter().filter(|&&revealed| revealed).count() as u32
+    }
+
+    fn is_unique(&self, solution: &Board, mask: &[bool; Square::NUM]) -> bool {
+        let board = mask_to_board(solution, mask);
+        Sudoku::new(board).is_unique_solvable()
+    }
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 完成盤 `solution` のうち、`mask` で見せると指定されたマスだけを残した盤面を作る。
+fn mask_to_board(solution: &Board, mask: &[bool; Square::NUM]) -> Board {
+    let mut inner = [None; Square::NUM];
+    for sq in Square::all() {
+        if mask[usize::from(sq.get())] {
+            inner[usize::from(sq.get())] = solution[sq];
+        }
+    }
+    Board::new(inner).expect("完成盤の部分集合は常に妥当な盤面")
+}
@@ -0,0 +1,102 @@
+/// 標準 Sudoku (3×3 ブロック、9×9 マス) の列・行・ブロック対応表。
+///
+/// `Square::col`/`row`/`block` 等が `const TABLE` 直書きに代わって参照する
+/// 内部実装。対応表はコンストラクタで一度だけ計算し、以降は配列参照による
+/// O(1) 引きとして使う。
+///
+/// **これは内部のルックアップテーブルをリテラル定数から計算済みテーブルに
+/// 置き換えるリファクタリングであり、chunk1-2 が求めた 4×4/6×6/12×12/16×16
+/// など可変サイズ盤面のサポートは提供しない。** `Geometry` は `classic()`
+/// 経由でしか構築できず、`Square::NUM`/`Col::NUM`/`Row::NUM`/`Block::NUM`、
+/// および `Number`/`Bitboard` は引き続き辺の長さ9・マス数81決め打ちのまま
+/// である。可変盤面を実際に使えるようにするには、これらすべてを
+/// `Geometry` (または const generics) で貫通させる、本チケットとは別の
+/// 作業が要る。
+pub(crate) struct Geometry {
+    side: u8,
+    col_table: Vec<u8>,
+    row_table: Vec<u8>,
+    block_table: Vec<u8>,
+    block_base_table: Vec<u8>,
+    block_offset_table: Vec<u8>,
+}
+
+impl Geometry {
+    /// 標準 Sudoku (3×3 ブロック) のジオメトリを返す。
+    pub(crate) fn classic() -> &'static Self {
+        static CLASSIC: std::sync::OnceLock<Geometry> = std::sync::OnceLock::new();
+        CLASSIC.get_or_init(Self::build_classic)
+    }
+
+    /// 3×3 ブロック・9×9 マス決め打ちで対応表を計算する。
+    fn build_classic() -> Self {
+        const BOX_ROWS: usize = 3;
+        const BOX_COLS: usize = 3;
+        const SIDE: usize = BOX_ROWS * BOX_COLS;
+        const NUM_CELLS: usize = SIDE * SIDE;
+
+        let mut col_table = Vec::with_capacity(NUM_CELLS);
+        let mut row_table = Vec::with_capacity(NUM_CELLS);
+        let mut block_table = Vec::with_capacity(NUM_CELLS);
+        for i in 0..NUM_CELLS {
+            let col = i % SIDE;
+            let row = i / SIDE;
+            col_table.push(col as u8);
+            row_table.push(row as u8);
+            block_table.push((row / BOX_ROWS * BOX_COLS + col / BOX_COLS) as u8);
+        }
+
+        let mut block_base_table = Vec::with_capacity(SIDE);
+        for b in 0..SIDE {
+            let base_row = (b / BOX_COLS) * BOX_ROWS;
+            let base_col = (b % BOX_COLS) * BOX_COLS;
+            block_base_table.push((base_row * SIDE + base_col) as u8);
+        }
+
+        let mut block_offset_table = Vec::with_capacity(SIDE);
+        for i in 0..SIDE {
+            let r = i / BOX_COLS;
+            let c = i % BOX_COLS;
+            block_offset_table.push((r * SIDE + c) as u8);
+        }
+
+        Self {
+            side: SIDE as u8,
+            col_table,
+            row_table,
+            block_table,
+            block_base_table,
+            block_offset_table,
+        }
+    }
+
+    /// 盤面の一辺の長さを返す。
+    pub(crate) fn side(&self) -> u8 {
+        self.side
+    }
+
+    /// 内部値 `sq` が属する列の内部値を返す。
+    pub(crate) fn col_of(&self, sq: u8) -> u8 {
+        self.col_table[usize::from(sq)]
+    }
+
+    /// 内部値 `sq` が属する行の内部値を返す。
+    pub(crate) fn row_of(&self, sq: u8) -> u8 {
+        self.row_table[usize::from(sq)]
+    }
+
+    /// 内部値 `sq` が属するブロックの内部値を返す。
+    pub(crate) fn block_of(&self, sq: u8) -> u8 {
+        self.block_table[usize::from(sq)]
+    }
+
+    /// ブロック `block` の先頭マスの内部値を返す。
+    pub(crate) fn block_base(&self, block: u8) -> u8 {
+        self.block_base_table[usize::from(block)]
+    }
+
+    /// ブロック内 `i` 番目のマスの、先頭マスからのオフセットを返す。
+    pub(crate) fn block_offset(&self, i: u8) -> u8 {
+        self.block_offset_table[usize::from(i)]
+    }
+}
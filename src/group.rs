@@ -0,0 +1,67 @@
+use crate::square::*;
+
+/// 数独の派生ルール(バリアント)。
+///
+/// 標準ルールである列・行・ブロックの制約に加え、どのような制約グループを
+/// 課すかを切り替える。
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Variant {
+    /// 通常の数独。列・行・ブロックのみを制約とする。
+    Classic,
+    /// X-Sudoku。通常の制約に加え、2本の対角線にも 1〜9 が重複なく入る。
+    XSudoku,
+}
+
+impl Variant {
+    /// このバリアントが課す制約グループの総数。
+    pub(crate) fn group_count(self) -> usize {
+        match self {
+            Self::Classic => 27,
+            Self::XSudoku => 29,
+        }
+    }
+
+    /// マス `sq` が属する制約グループ番号の一覧を、`None` 埋めの固定長配列で返す。
+    ///
+    /// グループ番号は `0..9` が列、`9..18` が行、`18..27` がブロックに対応する。
+    /// `XSudoku` ではこれに加え、主対角線を `27`、副対角線を `28` とする。
+    /// このマス1つが属しうるグループ数はどのバリアントでも高々5つなので、
+    /// バックトラッキングの毎手で呼ばれるホットパスを考慮し `Vec` ではなく
+    /// 固定長配列で返す。
+    pub(crate) fn groups_of(self, sq: Square) -> [Option<usize>; 5] {
+        let mut groups = [None; 5];
+        let mut len = 0;
+        groups[len] = Some(usize::from(sq.col().get()));
+        len += 1;
+        groups[len] = Some(9 + usize::from(sq.row().get()));
+        len += 1;
+        groups[len] = Some(18 + usize::from(sq.block().get()));
+        len += 1;
+
+        if self == Self::XSudoku {
+            let (col, row) = (sq.col().get(), sq.row().get());
+            if col == row {
+                groups[len] = Some(27);
+                len += 1;
+            }
+            if col + row == 8 {
+                groups[len] = Some(28);
+                len += 1;
+            }
+        }
+        let _ = len;
+
+        groups
+    }
+
+    /// 制約グループ番号ごとに、それに属するマスの一覧を返す。
+    pub(crate) fn groups(self) -> Vec<Vec<Square>> {
+        let mut groups = vec![Vec::new(); self.group_count()];
+        for sq in Square::all() {
+            for g in self.groups_of(sq).into_iter().flatten() {
+                groups[g].push(sq);
+            }
+        }
+        groups
+    }
+}
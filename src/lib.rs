@@ -1,9 +1,16 @@
+mod annealing;
 mod bitboard;
 mod board;
+mod dlx;
+pub mod generator;
+mod geometry;
+mod group;
 mod macros;
 mod number;
 mod square;
 mod sudoku;
+mod symmetry;
+mod technique;
 mod used_mask;
 
 pub use self::board::*;
@@ -1,6 +1,68 @@
+use crate::geometry::Geometry;
 use crate::macros::assert_unchecked;
 
+/// `Square::peers` が返す、マス `sq` (内部値) と行・列・ブロックのいずれかを
+/// 共有する(`sq` 自身を除く)マスの内部値を、昇順で求める。
+const fn peers_of(sq: u8) -> [u8; 20] {
+    const fn col_of(sq: u8) -> u8 {
+        sq % 9
+    }
+    const fn row_of(sq: u8) -> u8 {
+        sq / 9
+    }
+    const fn block_of(sq: u8) -> u8 {
+        (row_of(sq) / 3) * 3 + (col_of(sq) / 3)
+    }
+    const fn contains(peers: &[u8; 20], len: usize, value: u8) -> bool {
+        let mut i = 0;
+        while i < len {
+            if peers[i] == value {
+                return true;
+            }
+            i += 1;
+        }
+        false
+    }
+
+    let col = col_of(sq);
+    let row = row_of(sq);
+    let block = block_of(sq);
+
+    let mut peers = [0_u8; 20];
+    let mut len = 0;
+
+    let mut i = 0;
+    while i < 81 {
+        let candidate = i as u8;
+        if candidate != sq
+            && (col_of(candidate) == col || row_of(candidate) == row || block_of(candidate) == block)
+            && !contains(&peers, len, candidate)
+        {
+            peers[len] = candidate;
+            len += 1;
+        }
+        i += 1;
+    }
+
+    peers
+}
+
+/// 各マスの `peers_of` をまとめたテーブル。コンパイル時に一度だけ計算する。
+const PEER_TABLE: [[u8; 20]; 81] = {
+    let mut table = [[0_u8; 20]; 81];
+    let mut i = 0;
+    while i < 81 {
+        table[i] = peers_of(i as u8);
+        i += 1;
+    }
+    table
+};
+
 /// マス。
+///
+/// 標準 Sudoku (3×3 ブロック、9×9 マス) 固定。`col()`/`row()`/`block()` の
+/// 内部実装は `Geometry` を介するが、`NUM`/`MAX_VALUE` 自体は依然として
+/// 81 決め打ちであり、4×4 や 6×6 など他サイズの盤面はまだ作れない。
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct Square(u8);
 
@@ -29,7 +91,8 @@ impl Square {
 
     /// 列と行を指定してマスを作る。
     pub fn from_col_row(col: Col, row: Row) -> Self {
-        let inner = 9 * row.get() + col.get();
+        let side = Geometry::classic().side();
+        let inner = side * row.get() + col.get();
         unsafe { Self::new_unchecked(inner) }
     }
 
@@ -40,58 +103,19 @@ impl Square {
 
     /// マスが属する列を返す。
     pub fn col(self) -> Col {
-        #[rustfmt::skip]
-        const TABLE: [u8; Square::NUM] = [
-            0, 1, 2, 3, 4, 5, 6, 7, 8,
-            0, 1, 2, 3, 4, 5, 6, 7, 8,
-            0, 1, 2, 3, 4, 5, 6, 7, 8,
-            0, 1, 2, 3, 4, 5, 6, 7, 8,
-            0, 1, 2, 3, 4, 5, 6, 7, 8,
-            0, 1, 2, 3, 4, 5, 6, 7, 8,
-            0, 1, 2, 3, 4, 5, 6, 7, 8,
-            0, 1, 2, 3, 4, 5, 6, 7, 8,
-            0, 1, 2, 3, 4, 5, 6, 7, 8,
-        ];
-
-        let inner = TABLE[usize::from(self.0)];
+        let inner = Geometry::classic().col_of(self.0);
         unsafe { Col::new_unchecked(inner) }
     }
 
     /// マスが属する行を返す。
     pub fn row(self) -> Row {
-        #[rustfmt::skip]
-        const TABLE: [u8; Square::NUM] = [
-            0, 0, 0, 0, 0, 0, 0, 0, 0,
-            1, 1, 1, 1, 1, 1, 1, 1, 1,
-            2, 2, 2, 2, 2, 2, 2, 2, 2,
-            3, 3, 3, 3, 3, 3, 3, 3, 3,
-            4, 4, 4, 4, 4, 4, 4, 4, 4,
-            5, 5, 5, 5, 5, 5, 5, 5, 5,
-            6, 6, 6, 6, 6, 6, 6, 6, 6,
-            7, 7, 7, 7, 7, 7, 7, 7, 7,
-            8, 8, 8, 8, 8, 8, 8, 8, 8,
-        ];
-
-        let inner = TABLE[usize::from(self.0)];
+        let inner = Geometry::classic().row_of(self.0);
         unsafe { Row::new_unchecked(inner) }
     }
 
     /// マスが属するブロックを返す。
     pub fn block(self) -> Block {
-        #[rustfmt::skip]
-        const TABLE: [u8; Square::NUM] = [
-            0, 0, 0, 1, 1, 1, 2, 2, 2,
-            0, 0, 0, 1, 1, 1, 2, 2, 2,
-            0, 0, 0, 1, 1, 1, 2, 2, 2,
-            3, 3, 3, 4, 4, 4, 5, 5, 5,
-            3, 3, 3, 4, 4, 4, 5, 5, 5,
-            3, 3, 3, 4, 4, 4, 5, 5, 5,
-            6, 6, 6, 7, 7, 7, 8, 8, 8,
-            6, 6, 6, 7, 7, 7, 8, 8, 8,
-            6, 6, 6, 7, 7, 7, 8, 8, 8,
-        ];
-
-        let inner = TABLE[usize::from(self.0)];
+        let inner = Geometry::classic().block_of(self.0);
         unsafe { Block::new_unchecked(inner) }
     }
 
@@ -102,17 +126,19 @@ impl Square {
 
     /// 指定した列に属する全てのマスを昇順で返す。
     pub fn col_all(col: Col) -> [Self; 9] {
+        let side = Geometry::classic().side();
         let base = col.get();
 
         std::array::from_fn(|i| {
-            let inner = base + 9 * i as u8;
+            let inner = base + side * i as u8;
             unsafe { Self::new_unchecked(inner) }
         })
     }
 
     /// 指定した行に属する全てのマスを昇順で返す。
     pub fn row_all(row: Row) -> [Self; 9] {
-        let base = 9 * row.get();
+        let side = Geometry::classic().side();
+        let base = side * row.get();
 
         std::array::from_fn(|i| {
             let inner = base + i as u8;
@@ -122,16 +148,30 @@ impl Square {
 
     /// 指定したブロックに属する全てのマスを昇順で返す。
     pub fn block_all(block: Block) -> [Self; 9] {
-        const BASE_TABLE: [u8; Block::NUM] = [0, 3, 6, 27, 30, 33, 54, 57, 60];
-        const OFFSET_TABLE: [u8; 9] = [0, 1, 2, 9, 10, 11, 18, 19, 20];
-
-        let base = BASE_TABLE[usize::from(block.get())];
+        let geometry = Geometry::classic();
+        let base = geometry.block_base(block.get());
 
         std::array::from_fn(|i| {
-            let inner = base + OFFSET_TABLE[i];
+            let inner = base + geometry.block_offset(i as u8);
             unsafe { Self::new_unchecked(inner) }
         })
     }
+
+    /// マスと行・列・ブロックのいずれかを共有する(マス自身を除く)20 マスを、
+    /// 昇順で返す。コンパイル時に計算したテーブルを引くだけなので O(1)。
+    pub fn peers(self) -> [Self; 20] {
+        let table = &PEER_TABLE[usize::from(self.0)];
+        std::array::from_fn(|i| unsafe { Self::new_unchecked(table[i]) })
+    }
+
+    /// マスが属する列・行・ブロックの 3 つのユニットを返す。
+    pub fn units(self) -> [[Self; 9]; 3] {
+        [
+            Self::col_all(self.col()),
+            Self::row_all(self.row()),
+            Self::block_all(self.block()),
+        ]
+    }
 }
 
 /// 列。
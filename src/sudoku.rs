@@ -1,11 +1,20 @@
+use std::time::{Duration, Instant};
+
 use rand::prelude::*;
 
+use crate::annealing;
 use crate::bitboard::Bitboard;
 use crate::board::Board;
+use crate::dlx::Dlx;
+use crate::group::Variant;
 use crate::number::Number;
 use crate::square::*;
+use crate::technique;
 use crate::used_mask::UsedMasks;
 
+pub use crate::symmetry::Symmetry;
+pub use crate::technique::Difficulty;
+
 /// 数独の局面。
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Sudoku {
@@ -13,10 +22,27 @@ pub struct Sudoku {
     used_masks: UsedMasks,
 }
 
+/// `Sudoku::solve_with_stats` が計測する、バックトラッキング探索のコスト。
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SolveStats {
+    /// 解けたかどうか。
+    pub solved: bool,
+    /// 展開した探索木のノード数(`solve_with_stats` の再帰呼び出し回数)。
+    pub nodes: u32,
+    /// 実際に数を置いて分岐した回数(伝播による確定は含まない)。
+    pub guesses: u32,
+    /// 伝播 (`propagate`) によって強制確定させたマスの総数。
+    pub propagations: u32,
+}
+
+/// `Sudoku::search_hard` のヒルクライムで、これだけ改善が続けて得られなければ
+/// 別の完成盤から探索し直す。
+const SEARCH_HARD_STALL_LIMIT: u32 = 500;
+
 impl Sudoku {
     /// 盤面を指定して局面を作る。
     pub fn new(board: Board) -> Self {
-        let mut used_masks = UsedMasks::all_unused();
+        let mut used_masks = UsedMasks::all_unused(board.variant());
         for sq in Square::all() {
             if let Some(num) = board[sq] {
                 used_masks.use_number(sq, num);
@@ -54,6 +80,11 @@ impl Sudoku {
     }
 
     fn solve_impl(&mut self, mut bb_vacant: Bitboard) -> bool {
+        // 候補数が1つのマスを強制確定させる。矛盾が見つかれば解けない。
+        let Some(propagated) = self.propagate(&mut bb_vacant) else {
+            return false;
+        };
+
         // 次の空きマスを得る。空きマスがなければ解けている。
         let Some(sq) = self.pop_best_vacant_square(&mut bb_vacant) else {
             return true;
@@ -68,10 +99,106 @@ impl Sudoku {
             self.remove_number(sq, num);
         }
 
-        // どの候補もダメなら解けない。
+        // どの候補もダメなら解けない。強制確定させた分も取り消す。
+        self.undo_propagate(propagated);
         false
     }
 
+    /// `solve` と同じ MRV 分岐バックトラッキングで局面を解きつつ、
+    /// 探索にかかったコストを `SolveStats` として計測する。
+    ///
+    /// 結果によらず、盤面は埋められるだけ埋められる。
+    pub fn solve_with_stats(&mut self) -> SolveStats {
+        let bb_vacant = calc_bb_vacant(&self.board);
+        let mut stats = SolveStats::default();
+        let solved = self.solve_with_stats_impl(bb_vacant, &mut stats);
+        stats.solved = solved;
+        stats
+    }
+
+    fn solve_with_stats_impl(&mut self, mut bb_vacant: Bitboard, stats: &mut SolveStats) -> bool {
+        stats.nodes += 1;
+
+        let Some(propagated) = self.propagate(&mut bb_vacant) else {
+            return false;
+        };
+        stats.propagations += propagated.len() as u32;
+
+        let Some(sq) = self.pop_best_vacant_square(&mut bb_vacant) else {
+            return true;
+        };
+
+        for num in self.used_masks.candidates(sq) {
+            stats.guesses += 1;
+            self.put_number(sq, num);
+            if self.solve_with_stats_impl(bb_vacant, stats) {
+                return true;
+            }
+            self.remove_number(sq, num);
+        }
+
+        self.undo_propagate(propagated);
+        false
+    }
+
+    /// Dancing Links (Algorithm X) を用いて局面を解くことを試み、解けたかどうかを返す。
+    ///
+    /// `solve` と異なり MRV 分岐バックトラッキングではなく厳密被覆探索を行う。
+    /// 結果によらず、盤面は埋められるだけ埋められる。
+    ///
+    /// `Dlx` は列・行・ブロック制約のみをモデル化しているため、`Variant::Classic`
+    /// の局面にしか使えない。他のバリアントで呼ぶとパニックする。
+    ///
+    /// # Panics
+    ///
+    /// `self.board().variant()` が `Variant::Classic` でない場合にパニックする。
+    pub fn solve_dlx(&mut self) -> bool {
+        assert_eq!(
+            self.board.variant(),
+            Variant::Classic,
+            "solve_dlx は Variant::Classic にのみ対応する",
+        );
+
+        let mut dlx = Dlx::new(&self.board);
+        let Some(row_ids) = dlx.solve_first() else {
+            return false;
+        };
+
+        for row_id in row_ids {
+            let sq = unsafe { Square::new_unchecked((row_id / Number::NUM) as u8) };
+            let num = unsafe { Number::new_unchecked((row_id % Number::NUM + 1) as u8) };
+            self.put_number(sq, num);
+        }
+
+        true
+    }
+
+    /// 焼きなまし法 (Simulated Annealing) を用いて局面を解く。常に解けたとして扱う。
+    ///
+    /// `solve`/`solve_dlx` のような厳密探索と異なり、統計的な局所探索によって
+    /// 解を見つけるまで反復し続けるため、解をもたない局面に対しては停止しない。
+    ///
+    /// `annealing::solve` はエネルギー関数が列・行の重複数のみを数え、
+    /// ブロック制約はヒントを固定したシードで保証する設計になっており、
+    /// `Variant::Classic` にしか対応していない(対角線などの追加制約は
+    /// 考慮されない上、得られた盤面は `Board::new` で常に `Variant::Classic`
+    /// として組み直される)。他のバリアントで呼ぶとパニックする。
+    ///
+    /// # Panics
+    ///
+    /// `self.board().variant()` が `Variant::Classic` でない場合にパニックする。
+    pub fn solve_annealing(&mut self) -> bool {
+        assert_eq!(
+            self.board.variant(),
+            Variant::Classic,
+            "solve_annealing は Variant::Classic にのみ対応する",
+        );
+
+        let board = annealing::solve(&self.board);
+        *self = Self::new(board);
+        true
+    }
+
     /// 局面が解けるかどうかを返す。一意性は問わない。
     ///
     /// 呼び出し前後で局面は変化しない。
@@ -81,60 +208,123 @@ impl Sudoku {
     }
 
     fn is_solvable_impl(&mut self, mut bb_vacant: Bitboard) -> bool {
+        let Some(propagated) = self.propagate(&mut bb_vacant) else {
+            return false;
+        };
+
         let Some(sq) = self.pop_best_vacant_square(&mut bb_vacant) else {
+            self.undo_propagate(propagated);
             return true;
         };
 
+        let mut ok = false;
         for num in self.used_masks.candidates(sq) {
             self.put_number(sq, num);
-            let ok = self.is_solvable_impl(bb_vacant);
+            ok = self.is_solvable_impl(bb_vacant);
             self.remove_number(sq, num);
             if ok {
-                return true;
+                break;
             }
         }
 
-        false
+        self.undo_propagate(propagated);
+        ok
+    }
+
+    /// `bb_vacant` 内で候補数が1つしかないマスを見つけ次第確定させることを、
+    /// 確定できるマスがなくなるまで繰り返す。
+    ///
+    /// 候補数が0個の空きマス(矛盾)を検出した場合、確定させた分を取り消した上で
+    /// `None` を返す。そうでなければ、確定させた `(マス, 数)` の列を返す
+    /// (`undo_propagate` による取り消しに使う)。
+    fn propagate(&mut self, bb_vacant: &mut Bitboard) -> Option<Vec<(Square, Number)>> {
+        let mut propagated = Vec::new();
+
+        loop {
+            let mut forced = None;
+            for sq in bb_vacant.iter() {
+                match self.used_masks.candidate_count(sq) {
+                    0 => {
+                        self.undo_propagate(propagated);
+                        return None;
+                    }
+                    1 => {
+                        let num = self.used_masks.candidates(sq).next().unwrap();
+                        forced = Some((sq, num));
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+
+            let Some((sq, num)) = forced else {
+                break;
+            };
+            self.put_number(sq, num);
+            bb_vacant.remove(sq);
+            propagated.push((sq, num));
+        }
+
+        Some(propagated)
+    }
+
+    /// `propagate` で確定させたマスを元に戻す。
+    fn undo_propagate(&mut self, propagated: Vec<(Square, Number)>) {
+        for (sq, num) in propagated.into_iter().rev() {
+            self.remove_number(sq, num);
+        }
     }
 
     /// 局面が一意に解けるかどうかを返す。
     ///
     /// 呼び出し前後で局面は変化しない。
     pub fn is_unique_solvable(&mut self) -> bool {
+        self.count_solutions(2) == 1
+    }
+
+    /// 局面が持つ全ての解を、探索順に遅延列挙するイテレータを返す。
+    ///
+    /// 返されたイテレータは複製した局面上で探索するため、`self` は変化しない。
+    ///
+    /// `solve_dlx` が使う `Dlx` の構造は流用しない。`Dlx::search` は
+    /// 最初の解が見つかった時点で即終了する素朴な再帰であり、
+    /// `next()` 呼び出しのたびに中断点から再開する遅延列挙はサポートしない
+    /// ため、流用するには `Dlx` 自体に中断可能なイテレータ状態を持たせる
+    /// 作り直しが要る。ここでは `MRV` バックトラッキングのスタックを
+    /// `Solutions` として直接保持することで、`solve`/`is_solvable` と
+    /// 同じ MRV のマス選択・候補計算をそのまま再利用しつつ中断・再開を実現している。
+    /// `solve`/`is_solvable` と異なり、候補数1のマスの伝播 (`propagate`) は行わない。
+    pub fn solutions(&self) -> Solutions {
+        Solutions::new(self.clone())
+    }
+
+    /// 局面が持つ解の個数を数える。ただし `limit` 個に達した時点で打ち切る。
+    ///
+    /// 呼び出し前後で局面は変化しない。
+    pub fn count_solutions(&mut self, limit: u32) -> u32 {
         let bb_vacant = calc_bb_vacant(&self.board);
-        self.is_unique_solvable_impl(bb_vacant)
+        self.count_solutions_impl(bb_vacant, limit)
     }
 
-    fn is_unique_solvable_impl(&mut self, bb_vacant: Bitboard) -> bool {
-        #[derive(Debug)]
-        struct Search<'a> {
-            sudoku: &'a mut Sudoku,
-            count: u32,
-        }
-        impl<'a> Search<'a> {
-            fn new(sudoku: &'a mut Sudoku) -> Self {
-                Self { sudoku, count: 0 }
-            }
-            fn search(&mut self, mut bb_vacant: Bitboard) {
-                let Some(sq) = self.sudoku.pop_best_vacant_square(&mut bb_vacant) else {
-                    self.count += 1;
-                    return;
-                };
-                for num in self.sudoku.used_masks.candidates(sq) {
-                    self.sudoku.put_number(sq, num);
-                    self.search(bb_vacant);
-                    self.sudoku.remove_number(sq, num);
-                    if self.count >= 2 {
-                        return;
-                    }
-                }
-            }
+    fn count_solutions_impl(&mut self, mut bb_vacant: Bitboard, limit: u32) -> u32 {
+        if limit == 0 {
+            return 0;
         }
 
-        let mut search = Search::new(self);
-        search.search(bb_vacant);
+        let Some(sq) = self.pop_best_vacant_square(&mut bb_vacant) else {
+            return 1;
+        };
 
-        search.count == 1
+        let mut count = 0;
+        for num in self.used_masks.candidates(sq) {
+            self.put_number(sq, num);
+            count += self.count_solutions_impl(bb_vacant, limit - count);
+            self.remove_number(sq, num);
+            if count >= limit {
+                break;
+            }
+        }
+        count
     }
 
     /// 一意に解ける局面をランダムに生成し、その局面および解を返す。
@@ -185,6 +375,178 @@ impl Sudoku {
         (sudoku, solution)
     }
 
+    /// `symmetry` で指定した対称性を保ったまま、一意に解ける局面をランダムに
+    /// 生成し、その局面および解を返す。`hint_min` は最小ヒント数。
+    ///
+    /// マスを1つずつではなく、対称性が誘導する軌道(180度回転なら2マス、
+    /// 上下左右対称なら最大4マス)単位でまとめて消すことで、見た目に
+    /// 対称なヒント配置を保つ。軌道ごと消した後も一意解であることは、
+    /// その都度 `is_unique_solvable` で確かめる。
+    pub fn generate_unique_symmetric(symmetry: Symmetry, hint_min: u32) -> (Self, Self) {
+        let mut rng = thread_rng();
+        let mut sudoku = Self::generate_solved(&mut rng);
+        let solution = sudoku.clone();
+        let mut hint = 81_u32;
+
+        let mut orbits = Vec::new();
+        let mut seen = [false; Square::NUM];
+        for sq in Square::all() {
+            if seen[usize::from(sq.get())] {
+                continue;
+            }
+            let orbit = symmetry.orbit(sq);
+            for &s in &orbit {
+                seen[usize::from(s.get())] = true;
+            }
+            orbits.push(orbit);
+        }
+        orbits.shuffle(&mut rng);
+
+        for orbit in orbits {
+            if hint <= hint_min {
+                break;
+            }
+            if hint.saturating_sub(orbit.len() as u32) < hint_min {
+                continue;
+            }
+
+            // 軌道全体を一旦消す。
+            let removed: Vec<(Square, Number)> = orbit
+                .iter()
+                .map(|&sq| (sq, sudoku.board[sq].unwrap()))
+                .collect();
+            for &(sq, num) in &removed {
+                sudoku.remove_number(sq, num);
+            }
+
+            // 一意解でなくなるなら、消したマスを全て置き直す。
+            if sudoku.is_unique_solvable() {
+                hint -= orbit.len() as u32;
+            } else {
+                for &(sq, num) in &removed {
+                    sudoku.put_number(sq, num);
+                }
+            }
+        }
+
+        (sudoku, solution)
+    }
+
+    /// 一意に解ける局面を、要求難度がちょうど `target` になるまで生成し直す。
+    /// その局面および解を返す。
+    ///
+    /// `generate_unique` が生成するのは常に `Variant::Classic` の局面であり、
+    /// `rate_difficulty` の要求と一致する。
+    pub fn generate_unique_with_difficulty(target: Difficulty) -> (Self, Self) {
+        loop {
+            let (sudoku, solution) = Self::generate_unique(0);
+            if sudoku.rate_difficulty() == target {
+                return (sudoku, solution);
+            }
+        }
+    }
+
+    /// 局面を論理手筋のみで解いた場合に要する最高難度を返す。
+    ///
+    /// 呼び出し前後で局面は変化しない(内部では複製を解く)。
+    ///
+    /// `technique::solve` が実装する手筋(`place` による候補除去も含む)は
+    /// 列・行・ブロックのみを制約として扱い、`Variant::Classic` にしか
+    /// 対応していない。他のバリアントではこの手筋が対角線などの追加制約を
+    /// 見落としたまま数を確定させてしまい、単なる難度の過大評価ではなく
+    /// バリアントの制約に違反した(無効な)結果を返しうる。そのため
+    /// `solve_dlx` 同様、`Variant::Classic` 以外ではパニックする。
+    ///
+    /// # Panics
+    ///
+    /// `self.board().variant()` が `Variant::Classic` でない場合にパニックする。
+    pub fn rate_difficulty(&self) -> Difficulty {
+        assert_eq!(
+            self.board.variant(),
+            Variant::Classic,
+            "rate_difficulty は Variant::Classic にのみ対応する",
+        );
+
+        let mut board = self.board.clone();
+        let mut used_masks = self.used_masks.clone();
+        technique::solve(&mut board, &mut used_masks)
+    }
+
+    /// `budget` の時間をかけて、`solve_with_stats` のノード数が最大になるような
+    /// 一意解をもつ局面を探す。見つかった局面とその `SolveStats` を返す。
+    /// `seed` は乱数シード。
+    ///
+    /// 手掛かりマスクに対するヒルクライム(改善する手のみ受理する局所探索)を
+    /// 行い、`SEARCH_HARD_STALL_LIMIT` 回改善が得られなければ、新しい
+    /// ランダムな完成盤から探索し直す(多スタート)。
+    pub fn search_hard(budget: Duration, seed: u64) -> (Board, SolveStats) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let deadline = Instant::now() + budget;
+
+        let mut best: Option<(Board, SolveStats)> = None;
+
+        loop {
+            let (board, stats) = Self::climb_hard(&mut rng, deadline);
+            let is_better = match &best {
+                Some((_, best_stats)) => stats.nodes > best_stats.nodes,
+                None => true,
+            };
+            if is_better {
+                best = Some((board, stats));
+            }
+            if Instant::now() >= deadline {
+                break;
+            }
+        }
+
+        best.expect("ヒルクライムを少なくとも1回は行う")
+    }
+
+    /// 1回分のヒルクライムを行い、得られた盤面とその `SolveStats` を返す。
+    fn climb_hard(rng: &mut StdRng, deadline: Instant) -> (Board, SolveStats) {
+        let (mut sudoku, solution) = Self::generate_unique(17);
+        let mut stats = sudoku.clone().solve_with_stats();
+        let mut stall = 0;
+
+        while Instant::now() < deadline && stall < SEARCH_HARD_STALL_LIMIT {
+            let sq = Square::all().choose(rng).copied().unwrap();
+
+            // 手掛かりを外すなら一意解が保たれるかを確かめ、保たれないなら元に戻す。
+            // 手掛かりを足すなら解における値で埋めるので、常に一意解が保たれる。
+            let reverted = if let Some(num) = sudoku.board[sq] {
+                sudoku.remove_number(sq, num);
+                if sudoku.is_unique_solvable() {
+                    false
+                } else {
+                    sudoku.put_number(sq, num);
+                    true
+                }
+            } else {
+                sudoku.put_number(sq, solution.board[sq].unwrap());
+                false
+            };
+
+            if reverted {
+                stall += 1;
+                continue;
+            }
+
+            let new_stats = sudoku.clone().solve_with_stats();
+            if new_stats.nodes > stats.nodes {
+                stats = new_stats;
+                stall = 0;
+            } else {
+                match sudoku.board[sq] {
+                    Some(num) => sudoku.remove_number(sq, num),
+                    None => sudoku.put_number(sq, solution.board[sq].unwrap()),
+                }
+                stall += 1;
+            }
+        }
+
+        (sudoku.board, stats)
+    }
+
     /// 既に解けている局面をランダムに生成する。
     fn generate_solved<R>(rng: &mut R) -> Self
     where
@@ -310,3 +672,91 @@ fn calc_bb_vacant(board: &Board) -> Bitboard {
     }
     bb_vacant
 }
+
+/// `Sudoku::solutions` が返す、解を一つずつ遅延生成するイテレータ。
+///
+/// 複製した局面上でバックトラッキング探索を行い、1 つ解が見つかるたびに
+/// 探索を中断して呼び出し元へ返す。次の `next()` 呼び出しで、中断した地点から
+/// 直近に置いた数を取り除いて探索を再開する。
+#[derive(Debug)]
+pub struct Solutions {
+    sudoku: Sudoku,
+    stack: Vec<SolutionsFrame>,
+    started: bool,
+}
+
+/// 探索木における 1 つのマスの選択を表す。
+#[derive(Debug)]
+struct SolutionsFrame {
+    sq: Square,
+    /// `sq` 自身を除いた、この階層より先の空きマス集合。
+    bb_vacant: Bitboard,
+    /// `sq` にまだ試していない候補。
+    candidates: std::collections::VecDeque<Number>,
+    /// 現在 `sq` に置かれている数(次回呼び出し時に取り除く)。
+    placed: Option<Number>,
+}
+
+impl Solutions {
+    fn new(sudoku: Sudoku) -> Self {
+        Self {
+            sudoku,
+            stack: Vec::new(),
+            started: false,
+        }
+    }
+
+    /// `bb_vacant` から最良の空きマスを選んでフレームを積む。
+    /// 空きマスがなければ局面は解けているので、そのまま盤面を返す。
+    fn push_frame(&mut self, mut bb_vacant: Bitboard) -> Option<Board> {
+        let Some(sq) = self.sudoku.pop_best_vacant_square(&mut bb_vacant) else {
+            return Some(self.sudoku.board.clone());
+        };
+
+        let candidates = self.sudoku.used_masks.candidates(sq).collect();
+        self.stack.push(SolutionsFrame {
+            sq,
+            bb_vacant,
+            candidates,
+            placed: None,
+        });
+
+        None
+    }
+}
+
+impl Iterator for Solutions {
+    type Item = Board;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            let bb_vacant = calc_bb_vacant(&self.sudoku.board);
+            if let Some(board) = self.push_frame(bb_vacant) {
+                return Some(board);
+            }
+        }
+
+        loop {
+            let top = self.stack.last_mut()?;
+
+            if let Some(num) = top.placed.take() {
+                self.sudoku.remove_number(top.sq, num);
+            }
+
+            let Some(num) = top.candidates.pop_front() else {
+                self.stack.pop();
+                continue;
+            };
+
+            let sq = top.sq;
+            let bb_vacant = top.bb_vacant;
+            top.placed = Some(num);
+            self.sudoku.put_number(sq, num);
+
+            if let Some(board) = self.push_frame(bb_vacant) {
+                return Some(board);
+            }
+        }
+    }
+}
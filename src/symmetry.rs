@@ -0,0 +1,44 @@
+use crate::square::*;
+
+/// 問題生成時に維持する、ヒント配置の対称性。
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Symmetry {
+    /// 180度回転対称。
+    Rotational180,
+    /// 左右対称。
+    Horizontal,
+    /// 上下対称。
+    Vertical,
+    /// 上下左右対称(2面性)。
+    Dihedral,
+}
+
+impl Symmetry {
+    /// マス `sq` と対称な位置にあるマスからなる軌道を、重複なく昇順で返す。
+    pub(crate) fn orbit(self, sq: Square) -> Vec<Square> {
+        let col = sq.col().get();
+        let row = sq.row().get();
+        let make = |c: u8, r: u8| {
+            Square::from_col_row(
+                unsafe { Col::new_unchecked(c) },
+                unsafe { Row::new_unchecked(r) },
+            )
+        };
+
+        let mut orbit = vec![sq];
+        match self {
+            Self::Rotational180 => orbit.push(make(8 - col, 8 - row)),
+            Self::Horizontal => orbit.push(make(8 - col, row)),
+            Self::Vertical => orbit.push(make(col, 8 - row)),
+            Self::Dihedral => {
+                orbit.push(make(8 - col, row));
+                orbit.push(make(col, 8 - row));
+                orbit.push(make(8 - col, 8 - row));
+            }
+        }
+
+        orbit.sort_by_key(|sq| sq.get());
+        orbit.dedup_by_key(|sq| sq.get());
+        orbit
+    }
+}
@@ -0,0 +1,288 @@
+use std::collections::HashSet;
+
+use crate::board::Board;
+use crate::number::Number;
+use crate::square::*;
+use crate::used_mask::UsedMasks;
+
+/// 論理手筋による解きやすさの難度。値が大きいほど難しい手筋を要する。
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord)]
+pub enum Difficulty {
+    /// 裸のシングル(そのマスに入りうる数が 1 つだけ)。
+    NakedSingle,
+    /// 隠れたシングル(ある列/行/ブロック内でその数が入りうるマスが 1 つだけ)。
+    HiddenSingle,
+    /// ロックされた候補(ポインティングペア)。
+    LockedCandidate,
+    /// 裸or隠れたペア。
+    Pair,
+    /// 手筋が尽き、以降は仮定(バックトラック)が必要。
+    NeedsGuessing,
+}
+
+/// 局面を論理手筋のみで解き進め、最後まで解けたなら要した最高難度を、
+/// 手筋が尽きて解き切れなければ `Difficulty::NeedsGuessing` を返す。
+///
+/// `board` と `used_masks` は実際に確定したマスの分だけ更新される。
+pub(crate) fn solve(board: &mut Board, used_masks: &mut UsedMasks) -> Difficulty {
+    let mut marks = build_marks(board, used_masks);
+    let mut hardest = Difficulty::NakedSingle;
+
+    loop {
+        if try_naked_single(board, used_masks, &mut marks) {
+            continue;
+        }
+        if try_hidden_single(board, used_masks, &mut marks) {
+            hardest = hardest.max(Difficulty::HiddenSingle);
+            continue;
+        }
+        if try_locked_candidate(board, &mut marks) {
+            hardest = hardest.max(Difficulty::LockedCandidate);
+            continue;
+        }
+        if try_pair(board, &mut marks) {
+            hardest = hardest.max(Difficulty::Pair);
+            continue;
+        }
+        break;
+    }
+
+    if board.is_solved() {
+        hardest
+    } else {
+        Difficulty::NeedsGuessing
+    }
+}
+
+fn idx(sq: Square) -> usize {
+    usize::from(sq.get())
+}
+
+/// 全ての列/行/ブロックを 1 つのユニットの並びとして返す。
+fn all_units() -> impl Iterator<Item = [Square; 9]> {
+    Col::all()
+        .into_iter()
+        .map(Square::col_all)
+        .chain(Row::all().into_iter().map(Square::row_all))
+        .chain(Block::all().into_iter().map(Square::block_all))
+}
+
+/// 各マスの候補数マスクを、現在の `used_masks` から組み立てる。
+/// 既に数が入っているマスは 0 とする。
+fn build_marks(board: &Board, used_masks: &UsedMasks) -> [u32; Square::NUM] {
+    std::array::from_fn(|i| {
+        let sq = unsafe { Square::new_unchecked(i as u8) };
+        if board[sq].is_some() {
+            0
+        } else {
+            used_masks.candidate_mask(sq)
+        }
+    })
+}
+
+/// マス `sq` に数 `num` を確定させ、盤面・使用状況・候補マスクを更新する。
+fn place(
+    board: &mut Board,
+    used_masks: &mut UsedMasks,
+    marks: &mut [u32; Square::NUM],
+    sq: Square,
+    num: Number,
+) {
+    board[sq] = Some(num);
+    used_masks.use_number(sq, num);
+    marks[idx(sq)] = 0;
+
+    let bit = 1 << (num.get() - 1);
+    for peer in Square::col_all(sq.col())
+        .into_iter()
+        .chain(Square::row_all(sq.row()))
+        .chain(Square::block_all(sq.block()))
+    {
+        marks[idx(peer)] &= !bit;
+    }
+}
+
+/// 裸のシングルを 1 つ適用する。適用できたかどうかを返す。
+fn try_naked_single(
+    board: &mut Board,
+    used_masks: &mut UsedMasks,
+    marks: &mut [u32; Square::NUM],
+) -> bool {
+    for sq in Square::all() {
+        if board[sq].is_some() {
+            continue;
+        }
+        let mask = marks[idx(sq)];
+        if mask.count_ones() == 1 {
+            let num = unsafe { Number::new_unchecked(mask.trailing_zeros() as u8 + 1) };
+            place(board, used_masks, marks, sq, num);
+            return true;
+        }
+    }
+    false
+}
+
+/// 隠れたシングルを 1 つ適用する。適用できたかどうかを返す。
+fn try_hidden_single(
+    board: &mut Board,
+    used_masks: &mut UsedMasks,
+    marks: &mut [u32; Square::NUM],
+) -> bool {
+    for unit in all_units() {
+        for d in 0..9_u32 {
+            let bit = 1 << d;
+
+            let mut found = None;
+            let mut count = 0;
+            for &sq in &unit {
+                if board[sq].is_none() && (marks[idx(sq)] & bit) != 0 {
+                    count += 1;
+                    found = Some(sq);
+                }
+            }
+
+            if count == 1 {
+                let sq = found.unwrap();
+                let num = unsafe { Number::new_unchecked(d as u8 + 1) };
+                place(board, used_masks, marks, sq, num);
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// ロックされた候補(ポインティングペア)を 1 つ適用する。適用できたかどうかを返す。
+///
+/// あるブロック内で、ある数が入りうるマスが 1 つの行(または列)に限られる場合、
+/// そのブロック外の同じ行(列)からその数の候補を除去する。
+fn try_locked_candidate(board: &Board, marks: &mut [u32; Square::NUM]) -> bool {
+    for block in Block::all() {
+        let cells = Square::block_all(block);
+
+        for d in 0..9_u32 {
+            let bit = 1 << d;
+            let in_block: Vec<Square> = cells
+                .into_iter()
+                .filter(|&sq| board[sq].is_none() && (marks[idx(sq)] & bit) != 0)
+                .collect();
+            if in_block.len() < 2 {
+                continue;
+            }
+
+            let rows: HashSet<u8> = in_block.iter().map(|sq| sq.row().get()).collect();
+            let cols: HashSet<u8> = in_block.iter().map(|sq| sq.col().get()).collect();
+
+            let mut changed = false;
+            if rows.len() == 1 {
+                let row = unsafe { Row::new_unchecked(*rows.iter().next().unwrap()) };
+                for sq in Square::row_all(row) {
+                    if sq.block().get() != block.get()
+                        && board[sq].is_none()
+                        && (marks[idx(sq)] & bit) != 0
+                    {
+                        marks[idx(sq)] &= !bit;
+                        changed = true;
+                    }
+                }
+            }
+            if cols.len() == 1 {
+                let col = unsafe { Col::new_unchecked(*cols.iter().next().unwrap()) };
+                for sq in Square::col_all(col) {
+                    if sq.block().get() != block.get()
+                        && board[sq].is_none()
+                        && (marks[idx(sq)] & bit) != 0
+                    {
+                        marks[idx(sq)] &= !bit;
+                        changed = true;
+                    }
+                }
+            }
+
+            if changed {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// 裸or隠れたペアを 1 つ適用する。適用できたかどうかを返す。
+fn try_pair(board: &Board, marks: &mut [u32; Square::NUM]) -> bool {
+    for unit in all_units() {
+        // 裸のペア: ユニット内の 2 マスがどちらも同じ 2 候補マスクをもつなら、
+        // その 2 数を他のマスの候補から除去できる。
+        for i in 0..9 {
+            let sq_i = unit[i];
+            if board[sq_i].is_some() {
+                continue;
+            }
+            let mask_i = marks[idx(sq_i)];
+            if mask_i.count_ones() != 2 {
+                continue;
+            }
+
+            for &sq_j in unit.iter().skip(i + 1) {
+                if board[sq_j].is_some() || marks[idx(sq_j)] != mask_i {
+                    continue;
+                }
+
+                let mut changed = false;
+                for &sq_k in &unit {
+                    if sq_k.get() == sq_i.get() || sq_k.get() == sq_j.get() {
+                        continue;
+                    }
+                    if board[sq_k].is_some() {
+                        continue;
+                    }
+                    let before = marks[idx(sq_k)];
+                    marks[idx(sq_k)] &= !mask_i;
+                    if marks[idx(sq_k)] != before {
+                        changed = true;
+                    }
+                }
+                if changed {
+                    return true;
+                }
+            }
+        }
+
+        // 隠れたペア: ユニット内で 2 つの数がちょうど同じ 2 マスにしか
+        // 入りえないなら、その 2 マスの候補をその 2 数だけに絞れる。
+        for d1 in 0..9_u32 {
+            let bit1 = 1 << d1;
+            let cells1: Vec<Square> = unit
+                .into_iter()
+                .filter(|&sq| board[sq].is_none() && (marks[idx(sq)] & bit1) != 0)
+                .collect();
+            if cells1.len() != 2 {
+                continue;
+            }
+
+            for d2 in (d1 + 1)..9_u32 {
+                let bit2 = 1 << d2;
+                let cells2: Vec<Square> = unit
+                    .into_iter()
+                    .filter(|&sq| board[sq].is_none() && (marks[idx(sq)] & bit2) != 0)
+                    .collect();
+                if cells2 != cells1 {
+                    continue;
+                }
+
+                let mask = bit1 | bit2;
+                let mut changed = false;
+                for &sq in &cells1 {
+                    let before = marks[idx(sq)];
+                    marks[idx(sq)] &= mask;
+                    if marks[idx(sq)] != before {
+                        changed = true;
+                    }
+                }
+                if changed {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
@@ -1,46 +1,47 @@
+use crate::group::Variant;
 use crate::number::Number;
-use crate::square::*;
+use crate::square::Square;
 
 /// 数の使用状況を管理する。
+///
+/// 列・行・ブロックに加え、バリアントが課す追加の制約グループ(対角線など)
+/// ごとに使用済みマスクをもつ。
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub(crate) struct UsedMasks {
-    col_mask: UsedMask,
-    row_mask: UsedMask,
-    block_mask: UsedMask,
+    variant: Variant,
+    masks: Vec<UsedMask>,
 }
 
 impl UsedMasks {
     /// 解き終えた盤面に対応する `UsedMasks` を返す。
     #[allow(dead_code)]
-    pub(crate) fn all_used() -> Self {
+    pub(crate) fn all_used(variant: Variant) -> Self {
         Self {
-            col_mask: UsedMask::all_used(),
-            row_mask: UsedMask::all_used(),
-            block_mask: UsedMask::all_used(),
+            variant,
+            masks: vec![UsedMask::all_used(); variant.group_count()],
         }
     }
 
     /// 空の盤面に対応する `UsedMasks` を返す。
-    pub(crate) fn all_unused() -> Self {
+    pub(crate) fn all_unused(variant: Variant) -> Self {
         Self {
-            col_mask: UsedMask::all_unused(),
-            row_mask: UsedMask::all_unused(),
-            block_mask: UsedMask::all_unused(),
+            variant,
+            masks: vec![UsedMask::all_unused(); variant.group_count()],
         }
     }
 
     /// 空きマス `sq` に数 `num` を書いたとして状態を更新する。
     pub(crate) fn use_number(&mut self, sq: Square, num: Number) {
-        self.col_mask.use_number(sq.col().get(), num);
-        self.row_mask.use_number(sq.row().get(), num);
-        self.block_mask.use_number(sq.block().get(), num);
+        for g in self.variant.groups_of(sq).into_iter().flatten() {
+            self.masks[g].use_number(num);
+        }
     }
 
     /// マス `sq` に書かれた数 `num` を消したとして状態を更新する。
     pub(crate) fn unuse_number(&mut self, sq: Square, num: Number) {
-        self.col_mask.unuse_number(sq.col().get(), num);
-        self.row_mask.unuse_number(sq.row().get(), num);
-        self.block_mask.unuse_number(sq.block().get(), num);
+        for g in self.variant.groups_of(sq).into_iter().flatten() {
+            self.masks[g].unuse_number(num);
+        }
     }
 
     /// マス `sq` に数 `num` を置く手が合法かどうかを返す。
@@ -70,41 +71,41 @@ impl UsedMasks {
         self.candidate_mask(sq).count_ones()
     }
 
-    fn candidate_mask(&self, sq: Square) -> u32 {
-        const MASK_NUMS: u32 = (1 << 9) - 1;
-
-        let col_mask = (self.col_mask.0 >> (9 * sq.col().get())) as u32;
-        let row_mask = (self.row_mask.0 >> (9 * sq.row().get())) as u32;
-        let block_mask = (self.block_mask.0 >> (9 * sq.block().get())) as u32;
-
-        col_mask & row_mask & block_mask & MASK_NUMS
+    pub(crate) fn candidate_mask(&self, sq: Square) -> u32 {
+        self.variant
+            .groups_of(sq)
+            .into_iter()
+            .flatten()
+            .fold(UsedMask::FULL, |mask, g| mask & self.masks[g].0)
     }
 }
 
-/// 列or行orブロックに関する使用済みの数のマスク。
+/// 1つの制約グループに関する使用済みの数のマスク。
 ///
 /// 内部的には未使用の数を 1 とする。
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
-struct UsedMask(u128);
+struct UsedMask(u32);
 
 impl UsedMask {
-    /// 解き終えた盤面に対応するマスクを返す。
+    const FULL: u32 = (1 << Number::NUM) - 1;
+
+    /// 解き終えたグループに対応するマスクを返す。
     fn all_used() -> Self {
         Self(0)
     }
 
-    /// 空の盤面に対応するマスクを返す。
+    /// 空のグループに対応するマスクを返す。
     fn all_unused() -> Self {
-        Self((1 << 81) - 1)
+        Self(Self::FULL)
     }
 
-    /// `i` 番目の列or行orブロックについて数 `num` を使用済みとする。
-    fn use_number(&mut self, i: u8, num: Number) {
-        self.0 &= !(1 << (9 * i + num.get() - 1));
+    /// 数 `num` を使用済みとする。
+    fn use_number(&mut self, num: Number) {
+        self.0 &= !(1 << (num.get() - 1));
     }
 
-    /// `i` 番目の列or行orブロックについて数 `num` を未使用とする。
-    fn unuse_number(&mut self, i: u8, num: Number) {
-        self.0 |= 1 << (9 * i + num.get() - 1);
+    /// 数 `num` を未使用とする。
+    fn unuse_number(&mut self, num: Number) {
+        self.0 |= 1 << (num.get() - 1);
     }
 }